@@ -0,0 +1,63 @@
+//! An allocator-parametric smart pointer that actually reclaims its memory.
+
+use std::ops::{Deref, DerefMut};
+use std::ptr;
+
+use super::{Allocator, Block};
+
+/// A value allocated from, and owned by, a specific `Allocator`.
+///
+/// Unlike `Allocated`, `Box` remembers the allocator it came from (and the
+/// `Block` backing it), so on `Drop` it first runs `T`'s destructor in
+/// place and then calls `A::deallocate_raw` to actually return the memory.
+/// This mirrors the ergonomics of the stable allocator-API `Box::new_in`
+/// and is what makes the free-list/slab allocators (`FreeList`,
+/// `FixedSizeBlock`) usable without
+/// manual `deallocate_raw` calls.
+pub struct Box<'a, T: 'a, A: 'a + Allocator> {
+    allocator: &'a A,
+    block: Block,
+    item: *mut T,
+}
+
+impl<'a, T, A: Allocator> Box<'a, T, A> {
+    /// Creates a `Box` taking ownership of the `T` at `item`, stored in `block` (obtained from `allocator`).
+    ///
+    /// The caller must ensure `block` is large enough to hold a `T` and that
+    /// a valid `T` has already been (or is about to be) written at `item`.
+    pub(crate) fn new(allocator: &'a A, block: Block, item: *mut T) -> Self {
+        Box {
+            allocator: allocator,
+            block: block,
+            item: item,
+        }
+    }
+
+    /// The block of memory backing this value.
+    pub(crate) fn block(&self) -> Block {
+        self.block
+    }
+}
+
+impl<'a, T, A: Allocator> Deref for Box<'a, T, A> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.item }
+    }
+}
+
+impl<'a, T, A: Allocator> DerefMut for Box<'a, T, A> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.item }
+    }
+}
+
+impl<'a, T, A: Allocator> Drop for Box<'a, T, A> {
+    fn drop(&mut self) {
+        unsafe {
+            ptr::drop_in_place(self.item);
+            self.allocator.deallocate_raw(self.block);
+        }
+    }
+}
@@ -0,0 +1,151 @@
+//! A linear allocator that bumps from both ends of a single buffer.
+
+use std::cell::Cell;
+use std::mem;
+
+use super::{align_backward, align_forward, Allocator, AllocatorError, Block, BlockOwner,
+            HeapAllocator, HEAP};
+
+/// A double-ended linear allocator.
+///
+/// Backed by a single buffer obtained from a parent `Allocator`, `DoubleEnded`
+/// lets callers bump-allocate from either end of the same reservation:
+/// `allocate_raw` grows a low pointer upward, while `allocate_raw_back`
+/// grows a high pointer downward from the end. This is useful for
+/// segregating allocations with different lifetimes (e.g. long-lived data
+/// from one end, short-lived scratch from the other) inside one
+/// contiguous buffer. Out of memory is signalled when the two pointers
+/// would cross.
+pub struct DoubleEnded<'parent, A: 'parent + Allocator> {
+    allocator: &'parent A,
+    back: Cell<*mut u8>,
+    current: Cell<*mut u8>,
+    end: *mut u8,
+    start: *mut u8,
+}
+
+impl DoubleEnded<'static, HeapAllocator> {
+    /// Creates a new `DoubleEnded` backed by `size` bytes from the heap.
+    pub fn new(size: usize) -> Result<Self, AllocatorError> {
+        DoubleEnded::new_from(HEAP, size)
+    }
+}
+
+impl<'parent, A: Allocator> DoubleEnded<'parent, A> {
+    /// Creates a new `DoubleEnded` backed by `size` bytes from the allocator supplied.
+    pub fn new_from(alloc: &'parent A, size: usize) -> Result<Self, AllocatorError> {
+        match unsafe { alloc.allocate_raw(size, mem::align_of::<usize>()) } {
+            Ok(block) => {
+                let end = unsafe { block.ptr().offset(block.size() as isize) };
+
+                Ok(DoubleEnded {
+                    allocator: alloc,
+                    back: Cell::new(end),
+                    current: Cell::new(block.ptr()),
+                    end: end,
+                    start: block.ptr(),
+                })
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Attempts to allocate `size` bytes aligned to `align` from the high end of the buffer.
+    pub unsafe fn allocate_raw_back(&self, size: usize, align: usize) -> Result<Block, AllocatorError> {
+        let candidate = self.back.get().offset(-(size as isize));
+        let aligned_ptr = align_backward(candidate, align);
+
+        if aligned_ptr < self.current.get() {
+            Err(AllocatorError::OutOfMemory)
+        } else {
+            self.back.set(aligned_ptr);
+            Ok(Block::new(aligned_ptr, size, align))
+        }
+    }
+}
+
+unsafe impl<'parent, A: Allocator> Allocator for DoubleEnded<'parent, A> {
+    unsafe fn allocate_raw(&self, size: usize, align: usize) -> Result<Block, AllocatorError> {
+        let aligned_ptr = align_forward(self.current.get(), align);
+        let end_ptr = aligned_ptr.offset(size as isize);
+
+        if end_ptr > self.back.get() {
+            Err(AllocatorError::OutOfMemory)
+        } else {
+            self.current.set(end_ptr);
+            Ok(Block::new(aligned_ptr, size, align))
+        }
+    }
+
+    #[allow(unused_variables)]
+    unsafe fn deallocate_raw(&self, blk: Block) {
+        // no-op unless `blk` is the most recent allocation from either end,
+        // in which case that end's pointer can simply be rewound.
+        let ptr = blk.ptr();
+
+        if ptr.offset(blk.size() as isize) == self.current.get() {
+            self.current.set(ptr);
+        } else if ptr == self.back.get() {
+            self.back.set(ptr.offset(blk.size() as isize));
+        }
+    }
+}
+
+impl<'parent, A: Allocator> BlockOwner for DoubleEnded<'parent, A> {
+    fn owns_block(&self, blk: &Block) -> bool {
+        let ptr = blk.ptr();
+
+        ptr >= self.start && ptr <= self.end
+    }
+}
+
+impl<'parent, A: Allocator> Drop for DoubleEnded<'parent, A> {
+    /// Drops the `DoubleEnded`, returning its whole backing buffer to the parent.
+    fn drop(&mut self) {
+        let size = self.end as usize - self.start as usize;
+        if size > 0 {
+            unsafe {
+                self.allocator.deallocate_raw(Block::new(self.start, size, mem::align_of::<usize>()))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    #[test]
+    fn allocates_from_both_ends() {
+        let alloc = DoubleEnded::new(64).unwrap();
+
+        let front = alloc.allocate(1i32).unwrap();
+        let back = unsafe { alloc.allocate_raw_back(4, 1).unwrap() };
+
+        assert!(back.ptr() > &*front as *const i32 as *mut u8);
+    }
+
+    #[test]
+    fn out_of_memory_when_ends_cross() {
+        let alloc = DoubleEnded::new(8).unwrap();
+
+        unsafe {
+            let _front = alloc.allocate_raw(4, 1).unwrap();
+            let err = alloc.allocate_raw_back(8, 1).err().unwrap();
+            assert_eq!(err, AllocatorError::OutOfMemory);
+        }
+    }
+
+    #[test]
+    fn owning() {
+        let alloc = DoubleEnded::new(64).unwrap();
+
+        let front = alloc.allocate(1i32).unwrap();
+        assert!(alloc.owns(&front));
+
+        unsafe {
+            let back = alloc.allocate_raw_back(4, 1).unwrap();
+            assert!(alloc.owns_block(&back));
+        }
+    }
+}
@@ -0,0 +1,168 @@
+//! A fixed-size-block (slab) allocator for fast small allocations.
+
+use std::cell::Cell;
+use std::cmp;
+use std::ptr;
+
+use super::{Allocator, AllocatorError, Block};
+
+/// The size classes served directly by `FixedSizeBlock`.
+///
+/// Requests larger than the biggest class are forwarded straight to the
+/// parent allocator.
+const SIZE_CLASSES: [usize; 9] = [8, 16, 32, 64, 128, 256, 512, 1024, 2048];
+
+/// A free block within a size class, linked through the block itself.
+struct Node {
+    next: *mut Node,
+}
+
+/// A fixed-size-block (slab) allocator.
+///
+/// Fronts a parent allocator with a per-size-class free list, since
+/// repeated tiny `allocate` calls are common and a general free-list walk
+/// is slow. Each class keeps a head pointer to a singly-linked list of
+/// free blocks of exactly that size; the `next` pointer lives in the free
+/// block itself, so popping and pushing are O(1). Requests that don't fit
+/// any class, or whose class list is empty, fall back to the parent.
+///
+/// Every block in a class's free list is allocated from the parent with
+/// that class's own size as its alignment (not the alignment the caller
+/// happened to ask for), since class sizes are powers of two. That keeps
+/// blocks freely interchangeable within a class: any block aligned to the
+/// class size also satisfies every smaller alignment the class serves, so
+/// a block freed under a loose alignment can still be popped to satisfy a
+/// later, stricter one.
+pub struct FixedSizeBlock<'parent, A: 'parent + Allocator> {
+    allocator: &'parent A,
+    list_heads: [Cell<*mut Node>; 9],
+}
+
+impl<'parent, A: Allocator> FixedSizeBlock<'parent, A> {
+    /// Creates a new `FixedSizeBlock` fronting `alloc`.
+    pub fn new_from(alloc: &'parent A) -> Self {
+        FixedSizeBlock {
+            allocator: alloc,
+            list_heads: Default::default(),
+        }
+    }
+
+    /// The index of the smallest size class that fits `size` and `align`,
+    /// or `None` if no class is big enough.
+    fn class_for(size: usize, align: usize) -> Option<usize> {
+        let needed = cmp::max(size, align);
+        SIZE_CLASSES.iter().position(|&class| class >= needed)
+    }
+}
+
+// `FixedSizeBlock`'s raw list-head pointers all chain through blocks it owns
+// exclusively, so moving a `FixedSizeBlock` to another thread moves sole
+// access to those blocks with it. As with `FreeList`, the `A: Sync` bound
+// covers the non-raw-pointer field: `&'parent A` is only `Send` if `A` can
+// be accessed from another thread.
+unsafe impl<'parent, A: Allocator + Sync> Send for FixedSizeBlock<'parent, A> {}
+
+unsafe impl<'parent, A: Allocator> Allocator for FixedSizeBlock<'parent, A> {
+    unsafe fn allocate_raw(&self, size: usize, align: usize) -> Result<Block, AllocatorError> {
+        match Self::class_for(size, align) {
+            Some(idx) => {
+                let class = SIZE_CLASSES[idx];
+                let head = &self.list_heads[idx];
+
+                let ptr = head.get();
+                if !ptr.is_null() {
+                    head.set((*ptr).next);
+                    Ok(Block::new(ptr as *mut u8, class, align))
+                } else {
+                    // Always request the class's own alignment from the
+                    // parent, not the caller's, so every block in this
+                    // class's free list stays safe to hand out for any
+                    // alignment the class serves.
+                    self.allocator.allocate_raw(class, class)
+                }
+            }
+            None => self.allocator.allocate_raw(size, align),
+        }
+    }
+
+    unsafe fn deallocate_raw(&self, blk: Block) {
+        // Route by the class the block *would* round up to, not its raw
+        // reported size: a caller bridging from a size-agnostic API (e.g.
+        // `GlobalAlloc`) may hand back the original, pre-rounding request
+        // rather than the class size `allocate_raw` actually carved out.
+        match Self::class_for(blk.size(), blk.align()) {
+            Some(idx) => {
+                let head = &self.list_heads[idx];
+                let node = blk.ptr() as *mut Node;
+                *node = Node { next: head.get() };
+                head.set(node);
+            }
+            None => self.allocator.deallocate_raw(blk),
+        }
+    }
+}
+
+impl<'parent, A: Allocator> Drop for FixedSizeBlock<'parent, A> {
+    /// Returns every block currently sitting in a free list back to the parent.
+    fn drop(&mut self) {
+        for (idx, head) in self.list_heads.iter().enumerate() {
+            let class = SIZE_CLASSES[idx];
+            let mut cur = head.get();
+
+            while !cur.is_null() {
+                let next = unsafe { (*cur).next };
+                unsafe {
+                    self.allocator.deallocate_raw(Block::new(cur as *mut u8, class, class))
+                };
+                cur = next;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    #[test]
+    fn small_alloc_free_reuses_block() {
+        let slab = FixedSizeBlock::new_from(HEAP);
+
+        unsafe {
+            let blk = slab.allocate_raw(12, 1).unwrap();
+            assert_eq!(blk.size(), 16);
+            let ptr = blk.ptr();
+
+            slab.deallocate_raw(blk);
+
+            let blk2 = slab.allocate_raw(12, 1).unwrap();
+            assert_eq!(ptr, blk2.ptr());
+        }
+    }
+
+    #[test]
+    fn reused_block_honours_stricter_later_alignment() {
+        let slab = FixedSizeBlock::new_from(HEAP);
+
+        unsafe {
+            // freed under a loose alignment...
+            let blk = slab.allocate_raw(16, 1).unwrap();
+            slab.deallocate_raw(blk);
+
+            // ...must still come back properly aligned for a stricter request.
+            let blk2 = slab.allocate_raw(16, 16).unwrap();
+            assert_eq!(blk2.ptr() as usize % 16, 0);
+        }
+    }
+
+    #[test]
+    fn oversized_alloc_forwards_to_parent() {
+        let slab = FixedSizeBlock::new_from(HEAP);
+
+        unsafe {
+            let blk = slab.allocate_raw(4096, 1).unwrap();
+            assert_eq!(blk.size(), 4096);
+            slab.deallocate_raw(blk);
+        }
+    }
+}
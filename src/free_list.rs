@@ -0,0 +1,263 @@
+//! A free-list allocator supporting out-of-order deallocation and reuse.
+
+use std::cell::Cell;
+use std::cmp;
+use std::mem;
+use std::ptr;
+
+use super::{align_forward, Allocator, AllocatorError, Block, BlockOwner};
+
+/// The header of a free region, stored intrusively inside the region itself.
+struct Node {
+    size: usize,
+    next: *mut Node,
+}
+
+/// A free-list allocator.
+///
+/// Backed by a single buffer obtained from a parent `Allocator`, `FreeList`
+/// keeps an address-ordered, intrusive singly-linked list of free regions:
+/// each free node's size and `next` pointer live inside the free memory
+/// itself, so tracking free space costs no extra bookkeeping memory.
+/// `allocate_raw` walks the list first-fit, and `deallocate_raw` reinserts
+/// and coalesces with immediately adjacent neighbours to fight
+/// fragmentation.
+pub struct FreeList<'parent, A: 'parent + Allocator> {
+    allocator: &'parent A,
+    free_list: Cell<*mut Node>,
+    start: *mut u8,
+    end: *mut u8,
+}
+
+impl<'parent, A: Allocator> FreeList<'parent, A> {
+    /// Creates a new `FreeList` backed by `size` bytes from the allocator supplied.
+    pub fn new_from(alloc: &'parent A, size: usize) -> Result<Self, AllocatorError> {
+        match unsafe { alloc.allocate_raw(size, mem::align_of::<usize>()) } {
+            Ok(block) => {
+                let list = FreeList {
+                    allocator: alloc,
+                    free_list: Cell::new(ptr::null_mut()),
+                    start: block.ptr(),
+                    end: unsafe { block.ptr().offset(block.size() as isize) },
+                };
+
+                if block.size() >= mem::size_of::<Node>() {
+                    unsafe { list.push_free(block.ptr(), block.size()) };
+                }
+
+                Ok(list)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Rounds `size` up to be at least large enough to hold a `Node` header
+    /// and a multiple of `Node`'s alignment.
+    ///
+    /// Every free region's start address is already `Node`-aligned (by
+    /// induction: the backing buffer is, and every lead/tail remainder
+    /// carved below starts at an aligned boundary), so rounding the carved
+    /// size to a multiple of that alignment keeps the remainder's start
+    /// (`ptr + size`) aligned too, making it safe to later write a `Node`
+    /// there.
+    fn node_size(size: usize) -> usize {
+        let size = cmp::max(size, mem::size_of::<Node>());
+        let align = mem::align_of::<Node>();
+
+        (size + align - 1) & !(align - 1)
+    }
+
+    /// Inserts `[ptr, ptr + size)` back into the address-ordered free list,
+    /// coalescing it with an immediately adjacent predecessor and/or successor.
+    unsafe fn push_free(&self, ptr: *mut u8, size: usize) {
+        debug_assert!(size >= mem::size_of::<Node>());
+
+        let mut prev: *mut Node = ptr::null_mut();
+        let mut cur = self.free_list.get();
+
+        while !cur.is_null() && (cur as *mut u8) < ptr {
+            prev = cur;
+            cur = (*cur).next;
+        }
+
+        // Merge with the predecessor if the region starts right where it ends.
+        if !prev.is_null() && (prev as *mut u8).offset((*prev).size as isize) == ptr {
+            (*prev).size += size;
+
+            // The merged node may now abut `cur` too; merge forward as well.
+            if !cur.is_null() && (prev as *mut u8).offset((*prev).size as isize) == cur as *mut u8 {
+                (*prev).size += (*cur).size;
+                (*prev).next = (*cur).next;
+            }
+
+            return;
+        }
+
+        let mut node_size = size;
+        let mut next = cur;
+
+        // Merge with the successor if it starts right where the region ends.
+        if !cur.is_null() && ptr.offset(size as isize) == cur as *mut u8 {
+            node_size += (*cur).size;
+            next = (*cur).next;
+        }
+
+        let node = ptr as *mut Node;
+        *node = Node {
+            size: node_size,
+            next: next,
+        };
+
+        if prev.is_null() {
+            self.free_list.set(node);
+        } else {
+            (*prev).next = node;
+        }
+    }
+}
+
+// `FreeList`'s raw pointers (`start`, `end`, the `free_list` head) all point
+// into a buffer it owns exclusively, so moving a `FreeList` to another
+// thread moves sole access to that buffer with it - nothing else still
+// touches it from the old thread. The `A: Sync` bound covers the one
+// remaining field that isn't a raw pointer: `&'parent A` is only `Send`
+// if `A` can be accessed from another thread, i.e. if `A: Sync`.
+unsafe impl<'parent, A: Allocator + Sync> Send for FreeList<'parent, A> {}
+
+unsafe impl<'parent, A: Allocator> Allocator for FreeList<'parent, A> {
+    unsafe fn allocate_raw(&self, size: usize, align: usize) -> Result<Block, AllocatorError> {
+        // Every carved block must be big enough, and aligned enough, to
+        // later become a free node (see `node_size`).
+        let size = Self::node_size(size);
+
+        let mut prev: *mut Node = ptr::null_mut();
+        let mut cur = self.free_list.get();
+
+        while !cur.is_null() {
+            let node_ptr = cur as *mut u8;
+            let node_end = node_ptr.offset((*cur).size as isize);
+            let aligned_ptr = align_forward(node_ptr, align);
+
+            if aligned_ptr.offset(size as isize) <= node_end {
+                let next = (*cur).next;
+                if prev.is_null() {
+                    self.free_list.set(next);
+                } else {
+                    (*prev).next = next;
+                }
+
+                let lead = aligned_ptr as usize - node_ptr as usize;
+                if lead >= mem::size_of::<Node>() {
+                    self.push_free(node_ptr, lead);
+                }
+
+                let tail_ptr = aligned_ptr.offset(size as isize);
+                let tail = node_end as usize - tail_ptr as usize;
+                if tail >= mem::size_of::<Node>() {
+                    self.push_free(tail_ptr, tail);
+                }
+
+                return Ok(Block::new(aligned_ptr, size, align));
+            }
+
+            prev = cur;
+            cur = (*cur).next;
+        }
+
+        Err(AllocatorError::OutOfMemory)
+    }
+
+    unsafe fn deallocate_raw(&self, blk: Block) {
+        self.push_free(blk.ptr(), Self::node_size(blk.size()));
+    }
+}
+
+impl<'parent, A: Allocator> BlockOwner for FreeList<'parent, A> {
+    fn owns_block(&self, blk: &Block) -> bool {
+        let ptr = blk.ptr();
+
+        ptr >= self.start && ptr <= self.end
+    }
+}
+
+impl<'parent, A: Allocator> Drop for FreeList<'parent, A> {
+    /// Drops the `FreeList`, returning its whole backing buffer to the parent.
+    fn drop(&mut self) {
+        let size = self.end as usize - self.start as usize;
+        if size > 0 {
+            unsafe {
+                self.allocator.deallocate_raw(Block::new(self.start, size, mem::align_of::<usize>()))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    #[test]
+    fn alloc_and_free() {
+        let list = FreeList::new_from(HEAP, 1024).unwrap();
+        let a = list.allocate(1i32).unwrap();
+        let b = list.allocate(2i32).unwrap();
+        drop(a);
+        drop(b);
+    }
+
+    #[test]
+    fn reuses_freed_memory() {
+        let list = FreeList::new_from(HEAP, 64).unwrap();
+
+        unsafe {
+            let blk = list.allocate_raw(16, 1).unwrap();
+            let ptr = blk.ptr();
+            list.deallocate_raw(blk);
+
+            let blk2 = list.allocate_raw(16, 1).unwrap();
+            assert_eq!(ptr, blk2.ptr());
+        }
+    }
+
+    #[test]
+    fn coalesces_adjacent_frees() {
+        let list = FreeList::new_from(HEAP, 32).unwrap();
+
+        unsafe {
+            let a = list.allocate_raw(16, 1).unwrap();
+            let b = list.allocate_raw(16, 1).unwrap();
+
+            list.deallocate_raw(a);
+            list.deallocate_raw(b);
+
+            // the two freed, adjacent blocks should have coalesced, so a
+            // single allocation spanning both should now succeed.
+            let merged = list.allocate_raw(32, 1).unwrap();
+            assert_eq!(merged.size(), 32);
+        }
+    }
+
+    #[test]
+    fn odd_sized_carve_keeps_remainder_node_aligned() {
+        let list = FreeList::new_from(HEAP, 64).unwrap();
+
+        unsafe {
+            // an odd-sized request carved off a fresh, node-aligned buffer is
+            // rounded up by `node_size`, so the remainder it leaves still
+            // starts node-aligned and is safe to use as a free-list node.
+            let a = list.allocate_raw(17, 1).unwrap();
+            list.deallocate_raw(a);
+
+            let b = list.allocate_raw(8, 1).unwrap();
+            assert_eq!(b.ptr(), a.ptr());
+            list.deallocate_raw(b);
+        }
+    }
+
+    #[test]
+    fn out_of_memory() {
+        let list = FreeList::new_from(HEAP, 8).unwrap();
+        let (err, _) = list.allocate(0u64).err().unwrap();
+        assert_eq!(err, AllocatorError::OutOfMemory);
+    }
+}
@@ -0,0 +1,64 @@
+//! An adapter letting a crate `Allocator` be installed as `#[global_allocator]`.
+
+use std::alloc::{GlobalAlloc, Layout};
+use std::ptr;
+
+use super::{Allocator, Block, Locked};
+
+/// Wraps a `Locked`-protected `Allocator` so it can be used as `#[global_allocator]`.
+///
+/// Bridges `core::alloc::GlobalAlloc`'s `Layout`-based API to this crate's
+/// `allocate_raw`/`deallocate_raw`, mapping `AllocatorError::OutOfMemory` to
+/// a null pointer (as `GlobalAlloc` requires) instead of a panic.
+pub struct GlobalAdapter<A: Allocator>(pub Locked<A>);
+
+unsafe impl<A: Allocator> GlobalAlloc for GlobalAdapter<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        match self.0.allocate_raw(layout.size(), layout.align()) {
+            Ok(block) => block.ptr(),
+            Err(_) => ptr::null_mut(),
+        }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.0.deallocate_raw(Block::new(ptr, layout.size(), layout.align()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use std::alloc::{GlobalAlloc, Layout};
+
+    #[test]
+    fn alloc_and_dealloc_through_adapter() {
+        let adapter = GlobalAdapter(Locked::new(FreeList::new_from(HEAP, 64).unwrap()));
+        let layout = Layout::from_size_align(16, 1).unwrap();
+
+        unsafe {
+            let ptr = adapter.alloc(layout);
+            assert!(!ptr.is_null());
+            adapter.dealloc(ptr, layout);
+        }
+    }
+
+    #[test]
+    fn alloc_and_dealloc_through_slab_backed_adapter() {
+        // `FixedSizeBlock` rounds requests up to a size class, so `dealloc`
+        // only ever sees the caller's original, pre-rounding layout back -
+        // this must not end up freeing the wrong number of bytes.
+        let adapter = GlobalAdapter(Locked::new(FixedSizeBlock::new_from(HEAP)));
+        let layout = Layout::from_size_align(12, 1).unwrap();
+
+        unsafe {
+            let ptr = adapter.alloc(layout);
+            assert!(!ptr.is_null());
+            adapter.dealloc(ptr, layout);
+
+            // the freed class-16 block should be handed straight back out.
+            let ptr2 = adapter.alloc(layout);
+            assert_eq!(ptr, ptr2);
+            adapter.dealloc(ptr2, layout);
+        }
+    }
+}
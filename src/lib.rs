@@ -1,5 +1,7 @@
 #![feature(alloc, heap_api, ptr_as_ref, test)]
 
+use std::error::Error;
+use std::fmt;
 use std::mem;
 use std::ops::{Deref, DerefMut};
 use std::ptr;
@@ -8,6 +10,176 @@ use alloc::heap;
 
 extern crate alloc;
 
+mod boxed;
+mod double_ended;
+mod fixed_size_block;
+mod free_list;
+mod global_adapter;
+mod locked;
+mod scoped;
+
+pub use boxed::Box;
+pub use double_ended::DoubleEnded;
+pub use fixed_size_block::FixedSizeBlock;
+pub use free_list::FreeList;
+pub use global_adapter::GlobalAdapter;
+pub use locked::Locked;
+pub use scoped::Scoped;
+
+/// Errors that can occur while allocating memory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AllocatorError {
+    /// The allocator has no more memory left to satisfy the request.
+    OutOfMemory,
+    /// An allocator-specific error, carrying a human readable description.
+    AllocatorSpecific(String),
+}
+
+impl fmt::Display for AllocatorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            AllocatorError::OutOfMemory => write!(f, "out of memory"),
+            AllocatorError::AllocatorSpecific(ref msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl Error for AllocatorError {
+    fn description(&self) -> &str {
+        match *self {
+            AllocatorError::OutOfMemory => "out of memory",
+            AllocatorError::AllocatorSpecific(ref msg) => msg,
+        }
+    }
+}
+
+/// A raw block of memory handed out by an `Allocator`.
+///
+/// `Block` carries just enough information (pointer, size, align) for its
+/// owning allocator to account for it again on deallocation.
+#[derive(Debug, Clone, Copy)]
+pub struct Block {
+    ptr: *mut u8,
+    size: usize,
+    align: usize,
+}
+
+impl Block {
+    /// Creates a new `Block` describing `size` bytes at `ptr`, aligned to `align`.
+    pub fn new(ptr: *mut u8, size: usize, align: usize) -> Block {
+        Block {
+            ptr: ptr,
+            size: size,
+            align: align,
+        }
+    }
+
+    /// The start of this block.
+    pub fn ptr(&self) -> *mut u8 {
+        self.ptr
+    }
+
+    /// The size of this block, in bytes.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The alignment this block was allocated with.
+    pub fn align(&self) -> usize {
+        self.align
+    }
+}
+
+/// Rounds `ptr` up to the next multiple of `align`.
+#[inline]
+pub fn align_forward(ptr: *mut u8, align: usize) -> *mut u8 {
+    ((ptr as usize + align - 1) & !(align - 1)) as *mut u8
+}
+
+/// Rounds `ptr` down to the previous multiple of `align`.
+#[inline]
+pub fn align_backward(ptr: *mut u8, align: usize) -> *mut u8 {
+    ((ptr as usize) & !(align - 1)) as *mut u8
+}
+
+/// A source of raw memory blocks.
+///
+/// Everything else in this crate (scoping, free lists, slabs, locking, ...)
+/// is built on top of this trait.
+pub unsafe trait Allocator {
+    /// Attempts to allocate `size` bytes aligned to `align`.
+    ///
+    /// This is unsafe because the caller is responsible for eventually
+    /// passing the returned block back to `deallocate_raw`.
+    unsafe fn allocate_raw(&self, size: usize, align: usize) -> Result<Block, AllocatorError>;
+
+    /// Returns a block previously handed out by `allocate_raw` back to the allocator.
+    unsafe fn deallocate_raw(&self, blk: Block);
+
+    /// Attempts to allocate space for `val`.
+    ///
+    /// Returns a `Box` owning `val` and tied to this allocator, so dropping
+    /// it both runs `val`'s destructor and reclaims the memory via
+    /// `deallocate_raw`. On failure, returns `val` back alongside the error.
+    fn allocate<'a, T>(&'a self, val: T) -> Result<Box<'a, T, Self>, (AllocatorError, T)>
+        where Self: Sized
+    {
+        match unsafe { self.allocate_raw(mem::size_of::<T>(), mem::align_of::<T>()) } {
+            Ok(block) => {
+                let item = block.ptr() as *mut T;
+                unsafe { ptr::write(item, val) };
+
+                Ok(Box::new(self, block, item))
+            }
+            Err(err) => Err((err, val)),
+        }
+    }
+}
+
+/// An allocator that can report whether it owns a given allocation.
+pub trait BlockOwner {
+    /// Whether `blk` points into the memory this allocator hands out from.
+    ///
+    /// This is an address check only: it does not tell whether `blk` is
+    /// still allocated or has already been deallocated.
+    fn owns_block(&self, blk: &Block) -> bool;
+
+    /// Whether `item` was allocated from this allocator.
+    fn owns<T, A: Allocator>(&self, item: &Box<T, A>) -> bool {
+        self.owns_block(&item.block())
+    }
+}
+
+/// An `Allocator` that defers straight to the system heap.
+pub struct HeapAllocator;
+
+unsafe impl Allocator for HeapAllocator {
+    unsafe fn allocate_raw(&self, size: usize, align: usize) -> Result<Block, AllocatorError> {
+        let ptr = if size != 0 {
+            heap::allocate(size, align)
+        } else {
+            heap::EMPTY as *mut u8
+        };
+
+        if ptr.is_null() {
+            Err(AllocatorError::OutOfMemory)
+        } else {
+            Ok(Block::new(ptr, size, align))
+        }
+    }
+
+    unsafe fn deallocate_raw(&self, blk: Block) {
+        if blk.size() > 0 {
+            heap::deallocate(blk.ptr(), blk.size(), blk.align());
+        }
+    }
+}
+
+static HEAP_INSTANCE: HeapAllocator = HeapAllocator;
+
+/// The shared `HeapAllocator` instance, used as the default parent allocator.
+pub static HEAP: &'static HeapAllocator = &HEAP_INSTANCE;
+
 /// An item allocated by a custom allocator.
 pub struct Allocated<'a, T: 'a> {
     item: &'a mut T,
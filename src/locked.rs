@@ -0,0 +1,97 @@
+//! A spinlock wrapper making any `Allocator` safe to share across threads.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use super::{Allocator, AllocatorError, Block};
+
+/// Wraps an `Allocator` in a spinlock so `&Locked<A>` can be shared across threads.
+///
+/// Every other allocator in this crate is explicitly single-threaded. `Locked`
+/// takes the lock around each `allocate_raw`/`deallocate_raw` call, which is
+/// also the prerequisite for using one of them as a process-wide allocator
+/// via `GlobalAdapter`.
+pub struct Locked<A: Allocator> {
+    allocator: A,
+    locked: AtomicBool,
+}
+
+impl<A: Allocator> Locked<A> {
+    /// Creates a new `Locked` wrapping `allocator`, so it can live in a `static`.
+    pub const fn new(allocator: A) -> Self {
+        Locked {
+            allocator: allocator,
+            locked: AtomicBool::new(false),
+        }
+    }
+
+    /// Spins until the lock is acquired, then releases it once `f` returns.
+    fn with_lock<U, F>(&self, f: F) -> U
+        where F: FnOnce(&A) -> U
+    {
+        while self.locked.compare_and_swap(false, true, Ordering::Acquire) {
+            // spin
+        }
+
+        let u = f(&self.allocator);
+
+        self.locked.store(false, Ordering::Release);
+
+        u
+    }
+}
+
+unsafe impl<A: Allocator> Allocator for Locked<A> {
+    unsafe fn allocate_raw(&self, size: usize, align: usize) -> Result<Block, AllocatorError> {
+        self.with_lock(|a| a.allocate_raw(size, align))
+    }
+
+    unsafe fn deallocate_raw(&self, blk: Block) {
+        self.with_lock(|a| a.deallocate_raw(blk))
+    }
+}
+
+// Sound because every access to the wrapped allocator goes through the
+// spinlock in `with_lock`, so two threads can never touch it concurrently -
+// this is what actually makes `&Locked<A>` shareable across threads. The
+// `A: Send` bound is still required, mirroring `Mutex<T>: Sync` needing
+// `T: Send`: whichever thread holds the lock is the one that ends up
+// mutating `A`, so `A` itself must be safe to move between threads.
+unsafe impl<A: Allocator + Send> Sync for Locked<A> {}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use std::thread;
+
+    #[test]
+    fn alloc_and_free_through_lock() {
+        let list = Locked::new(FreeList::new_from(HEAP, 64).unwrap());
+
+        let a = list.allocate(1i32).unwrap();
+        let b = list.allocate(2i32).unwrap();
+        drop(a);
+        drop(b);
+    }
+
+    #[test]
+    fn shared_across_threads() {
+        // Leaked to get a `'static` reference, since `thread::spawn`'s
+        // closure must not borrow anything shorter-lived.
+        let list: &'static Locked<FreeList<'static, HeapAllocator>> =
+            std::boxed::Box::leak(std::boxed::Box::new(Locked::new(FreeList::new_from(HEAP, 4096)
+                .unwrap())));
+
+        let handles: Vec<_> = (0..4)
+            .map(|i| {
+                thread::spawn(move || {
+                    let val = list.allocate(i).unwrap();
+                    assert_eq!(*val, i);
+                })
+            })
+            .collect();
+
+        for h in handles {
+            h.join().unwrap();
+        }
+    }
+}
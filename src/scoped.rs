@@ -12,10 +12,23 @@ pub struct Scoped<'parent, A: 'parent + Allocator> {
     allocator: &'parent A,
     current: Cell<*mut u8>,
     end: *mut u8,
+    generation: Cell<u64>,
     root: bool,
     start: *mut u8,
 }
 
+/// A checkpoint captured by `Scoped::mark`, to be released with `Scoped::rewind`.
+///
+/// Unlike `scope`, marks don't require closures and can be taken and
+/// released in LIFO order across a frame, e.g. one mark per frame and one
+/// rewind at the frame's end. The `generation` lets `rewind` debug-assert
+/// that marks are released in reverse order of being taken.
+#[derive(Debug, Clone, Copy)]
+pub struct Marker {
+    ptr: *mut u8,
+    generation: u64,
+}
+
 impl Scoped<'static, HeapAllocator> {
     /// Creates a new `Scoped` backed by `size` bytes from the heap.
     pub fn new(size: usize) -> Result<Self, AllocatorError> {
@@ -32,6 +45,7 @@ impl<'parent, A: Allocator> Scoped<'parent, A> {
                 allocator: alloc,
                 current: Cell::new(block.ptr()),
                 end: unsafe { block.ptr().offset(block.size() as isize) },
+                generation: Cell::new(0),
                 root: true,
                 start: block.ptr(),
             }),
@@ -56,6 +70,7 @@ impl<'parent, A: Allocator> Scoped<'parent, A> {
             allocator: self.allocator,
             current: self.current.clone(),
             end: self.end,
+            generation: Cell::new(0),
             root: false,
             start: old,
         };
@@ -74,6 +89,38 @@ impl<'parent, A: Allocator> Scoped<'parent, A> {
     pub fn is_scoped(&self) -> bool {
         self.current.get().is_null()
     }
+
+    /// Captures the current bump pointer as a checkpoint.
+    ///
+    /// Unlike `scope`, marks don't require closures: they can be taken and
+    /// released in LIFO order, which suits e.g. frame-based loops (mark at
+    /// the start of a frame, rewind at its end).
+    pub fn mark(&self) -> Marker {
+        let generation = self.generation.get() + 1;
+        self.generation.set(generation);
+
+        Marker {
+            ptr: self.current.get(),
+            generation: generation,
+        }
+    }
+
+    /// Resets the bump pointer back to `m`, freeing everything allocated since the mark in O(1).
+    ///
+    /// A no-op if this allocator is currently `is_scoped()`. Debug-asserts
+    /// that `m` is the most recently taken, still-outstanding mark, since
+    /// marks must be rewound in reverse order of being taken.
+    pub fn rewind(&self, m: Marker) {
+        if self.is_scoped() {
+            return
+        }
+
+        debug_assert_eq!(self.generation.get(), m.generation,
+                          "Scoped::rewind() called out of order: marks must be rewound LIFO.");
+
+        self.current.set(m.ptr);
+        self.generation.set(m.generation - 1);
+    }
 }
 
 unsafe impl<'a, A: Allocator> Allocator for Scoped<'a, A> {
@@ -177,6 +224,30 @@ mod tests {
         let _big = in alloc.make_place().unwrap() { [0u8; 8_000_000] };
     }
 
+    #[test]
+    fn mark_rewind_frees_since_mark() {
+        let alloc = Scoped::new(8).unwrap();
+
+        let _kept = alloc.allocate(1i32).unwrap();
+        let m = alloc.mark();
+        let _scratch = alloc.allocate(2i32).unwrap();
+        alloc.rewind(m);
+
+        // the scratch allocation was freed by the rewind, so this should
+        // reuse the same space rather than run out of memory.
+        let _reused = alloc.allocate(3i32).unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn rewind_out_of_order_panics() {
+        let alloc = Scoped::new(64).unwrap();
+
+        let first = alloc.mark();
+        let _second = alloc.mark();
+        alloc.rewind(first);
+    }
+
     #[test]
     fn owning() {
         let alloc = Scoped::new(64).unwrap();